@@ -1,3 +1,13 @@
+use crate::runtime::{Mutex, MutexGuard};
+use crate::{ConnectionsCount, QueryAble, ReconnectBehavior};
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use redis::cluster::{ClusterClient, ClusterClientBuilder};
+use redis::cluster_async::ClusterConnection;
 use redis::RedisError;
 
 #[derive(Debug)]
@@ -6,7 +16,7 @@ enum CiseauxError {
     InvalidCmd,
 }
 
-fn is_read_only(cmd: &redis::Cmd) -> Result<bool, CiseauxError> {
+pub(crate) fn is_read_only(cmd: &redis::Cmd) -> Result<bool, CiseauxError> {
     match cmd.args_iter().take(1).next() {
         None => Err(CiseauxError::InvalidCmd),
         Some(a) => match a {
@@ -82,12 +92,184 @@ fn is_read_only(cmd: &redis::Cmd) -> Result<bool, CiseauxError> {
     }
 }
 
+/// An Init Struct to create a customized CiseauxCluster connections pool.
+/// This is like a Builder, but using public fields instead of functions, mirroring SingleInit
 #[derive(Debug)]
 pub struct ClusterInit {
+    /// The seed node URLs used to discover the cluster topology (e.g. "redis://127.0.0.1:7000")
+    pub nodes: Vec<String>,
+    /// By default, 4 connections in total (ConnectionsCount::Global(4)), opened once per pool,
+    /// so twice when auto_redirect_read is true
+    pub conns_count: ConnectionsCount,
+    /// By default, Instant Retry
+    pub reconnect_behavior: ReconnectBehavior,
+    /// When true, commands is_read_only() reports as read-only are routed to replica nodes
+    /// (via a dedicated set of connections opened in READONLY mode) instead of masters
     pub auto_redirect_read: bool,
 }
 
+impl ClusterInit {
+    /// This creates a ClusterInit with default settings and the provided seed node URLs
+    pub fn new(nodes: Vec<String>) -> ClusterInit {
+        ClusterInit {
+            nodes,
+            conns_count: ConnectionsCount::default(),
+            reconnect_behavior: ReconnectBehavior::default(),
+            auto_redirect_read: false,
+        }
+    }
+
+    /// Asynchronously discovers the cluster topology and opens the connections pool(s).
+    /// When auto_redirect_read is true, a second pool is opened in READONLY mode so
+    /// read-only commands can be served by replicas; otherwise every command uses the master pool.
+    pub async fn build(self) -> Result<CiseauxCluster, RedisError> {
+        let conns_count = self.conns_count.into_flat();
+
+        let master_client = ClusterClientBuilder::new(self.nodes.clone()).open()?;
+        let master_conns = open_conns(&master_client, conns_count).await?;
+
+        let (read_client, read_conns) = if self.auto_redirect_read {
+            let read_client = ClusterClientBuilder::new(self.nodes)
+                .readonly(true)
+                .open()?;
+            let read_conns = open_conns(&read_client, conns_count).await?;
+            (Some(Arc::new(read_client)), read_conns)
+        } else {
+            (None, Vec::new())
+        };
+
+        Ok(CiseauxCluster {
+            master_client: Arc::new(master_client),
+            read_client,
+            reconnect_behavior: self.reconnect_behavior,
+            auto_redirect_read: self.auto_redirect_read,
+            master_conns: Arc::new(master_conns),
+            read_conns: Arc::new(read_conns),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+async fn open_conns(
+    client: &ClusterClient,
+    count: usize,
+) -> Result<Vec<Mutex<ClusterConnection>>, RedisError> {
+    let mut conns_fut = Vec::with_capacity(count);
+    for _ in 0..count {
+        conns_fut.push(client.get_async_connection());
+    }
+    let mut conns = Vec::with_capacity(count);
+    for c in futures::future::join_all(conns_fut).await {
+        conns.push(Mutex::new(c?));
+    }
+    Ok(conns)
+}
+
+/// A connections pool to a Redis Cluster
 #[derive(Clone)]
-struct CiseauxCluster {
+pub struct CiseauxCluster {
+    master_client: Arc<ClusterClient>,
+    read_client: Option<Arc<ClusterClient>>,
+    reconnect_behavior: ReconnectBehavior,
     auto_redirect_read: bool,
+    master_conns: Arc<Vec<Mutex<ClusterConnection>>>,
+    read_conns: Arc<Vec<Mutex<ClusterConnection>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl CiseauxCluster {
+    /// Shortcut to ClusterInit::new
+    pub fn builder(nodes: Vec<String>) -> ClusterInit {
+        ClusterInit::new(nodes)
+    }
+
+    /// Shortcut to ClusterInit::new
+    pub fn init(nodes: Vec<String>) -> ClusterInit {
+        ClusterInit::new(nodes)
+    }
+
+    /// Create a new cluster pool using default settings
+    pub async fn new(nodes: Vec<String>) -> Result<CiseauxCluster, RedisError> {
+        ClusterInit::new(nodes).build().await
+    }
+
+    /// Asynchronously query QueryAble (trait, implemented for redis::Cmd and redis::Pipeline),
+    /// but in case of network error, will try to reconnect once to the same node (by default),
+    /// or follow the reconnect_behavior you provided.
+    ///
+    /// When auto_redirect_read is enabled and cmd.is_read_only() is true, the command is sent
+    /// through the replica (READONLY) pool to spread read load; everything else goes to masters.
+    /// MOVED/ASK redirections that happen along the way are handled by the underlying cluster
+    /// connection, not by CiseauxCluster itself.
+    pub async fn query<C: QueryAble, T: redis::FromRedisValue>(
+        &self,
+        cmd: C,
+    ) -> Result<T, RedisError> {
+        let use_read_pool = self.auto_redirect_read && cmd.is_read_only();
+        let pool = if use_read_pool {
+            &self.read_conns
+        } else {
+            &self.master_conns
+        };
+        let mut conn = pool[self.next.fetch_add(1, Ordering::AcqRel) % pool.len()]
+            .lock()
+            .await;
+        match cmd.query::<T, _>(&mut *conn).await {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                if crate::is_network_or_io_error(&e) {
+                    if self.reconnect_behavior == ReconnectBehavior::NoReconnect {
+                        return Err(e);
+                    }
+                    return self.retry_cmd(&mut conn, use_read_pool, cmd).await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    #[inline(always)]
+    async fn retry_cmd<'a, C: QueryAble, T: redis::FromRedisValue>(
+        &self,
+        conn: &mut MutexGuard<'a, ClusterConnection>,
+        use_read_pool: bool,
+        cmd: C,
+    ) -> Result<T, RedisError> {
+        match self.try_reconnect(conn, use_read_pool).await {
+            Ok(()) => cmd.query::<T, _>(&mut **conn).await,
+            Err(e) => {
+                if crate::is_network_or_io_error(&e) {
+                    match self.reconnect_behavior {
+                        ReconnectBehavior::RetryWaitRetry(d) => {
+                            crate::runtime::sleep(d.unwrap_or(crate::DEFAULT_WAIT_RETRY_DUR)).await;
+                            self.try_reconnect(conn, use_read_pool).await?;
+                            return cmd.query::<T, _>(&mut **conn).await;
+                        }
+                        _ => return Err(e),
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    #[inline(always)]
+    async fn try_reconnect<'a>(
+        &self,
+        conn: &mut MutexGuard<'a, ClusterConnection>,
+        use_read_pool: bool,
+    ) -> Result<(), RedisError> {
+        let client = if use_read_pool {
+            self.read_client.as_ref().unwrap_or(&self.master_client)
+        } else {
+            &self.master_client
+        };
+        match client.get_async_connection().await {
+            Ok(c) => {
+                **conn = c;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
 }
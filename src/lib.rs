@@ -1,7 +1,10 @@
-//! This is a simple asynchronous Redis Pool, currently only supports a connection pool to a Single Redis instance, and will probably provide Cluster support later.
+//! This is a simple asynchronous Redis Pool, supporting both a connection pool to a Single Redis
+//! instance (CiseauxSingle) and a Redis Cluster (CiseauxCluster).
 //! If you want to understand how to use it, see examples and/or CiseauxSingle struct.
 //!
-//! The library currently supports tokio only (Because of redis-rs, async-std support is coming), and require at least Rust 1.39
+//! The library supports both tokio and async-std, through the `runtime-tokio` (default) and
+//! `runtime-async-std` cargo features (disable default-features and enable `runtime-async-std`
+//! to use the latter). It requires at least Rust 1.39
 //!
 //! ```toml
 //! [dependencies]
@@ -38,10 +41,22 @@
 pub use redis;
 
 mod cluster;
+mod lock;
+#[cfg(feature = "mocks")]
+mod mock;
+mod pubsub;
+mod runtime;
 mod single;
 #[cfg(test)]
 mod tests;
+pub use cluster::CiseauxCluster;
+pub use cluster::ClusterInit;
+pub use lock::LockGuard;
+#[cfg(feature = "mocks")]
+pub use mock::{MockCommand, MockHandle};
+pub use pubsub::Subscription;
 pub use single::CiseauxSingle;
+pub use single::HealthCheck;
 pub use single::SingleInit;
 
 use std::time::Duration;
@@ -50,6 +65,12 @@ const DEFAULT_CONNS_COUNT: ConnectionsCount = ConnectionsCount::Global(4);
 const DEFAULT_RECONNECT_BEHAVIOR: ReconnectBehavior = ReconnectBehavior::InstantRetry;
 const DEFAULT_WAIT_RETRY_DUR: Duration = Duration::from_secs(2);
 
+/// Whether a RedisError means the underlying socket needs to be re-established (as opposed to,
+/// say, a command error), shared by CiseauxSingle and CiseauxCluster's retry/reconnect paths.
+pub(crate) fn is_network_or_io_error(error: &redis::RedisError) -> bool {
+    error.is_timeout() || error.is_connection_dropped() || error.is_io_error()
+}
+
 /// To change the default pool size
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ConnectionsCount {
@@ -94,70 +115,93 @@ impl std::default::Default for ReconnectBehavior {
 /// A trait that allow to have a single CiseauxSingle query, and not
 /// a query_x per redis commands types (redis::Cmd and redis::Pipeline).
 /// Implemented for redis::Cmd and redis::Pipeline (including &, and &mut)
+///
+/// Generic over the connection type so the same commands can be driven through
+/// a plain `redis::aio::Connection` (CiseauxSingle) or a cluster connection (CiseauxCluster).
 #[async_trait::async_trait]
 pub trait QueryAble {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError>;
+
+    /// Whether this command only reads data, and can therefore be safely routed to a replica
+    /// by CiseauxCluster when `auto_redirect_read` is enabled. Defaults to `false` (treated as
+    /// a write); only a single redis::Cmd can be classified this way, a redis::Pipeline always
+    /// goes to a master since it may mix reads and writes.
+    fn is_read_only(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for redis::Cmd {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
+    }
+
+    fn is_read_only(&self) -> bool {
+        crate::cluster::is_read_only(self).unwrap_or(false)
     }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for &redis::Cmd {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
+    }
+
+    fn is_read_only(&self) -> bool {
+        crate::cluster::is_read_only(self).unwrap_or(false)
     }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for &mut redis::Cmd {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
+    }
+
+    fn is_read_only(&self) -> bool {
+        crate::cluster::is_read_only(self).unwrap_or(false)
     }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for redis::Pipeline {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
     }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for &redis::Pipeline {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
     }
 }
 
 #[async_trait::async_trait]
 impl QueryAble for &mut redis::Pipeline {
-    async fn query<T: redis::FromRedisValue>(
+    async fn query<T: redis::FromRedisValue, C: redis::aio::ConnectionLike + Send>(
         &self,
-        conn: &mut redis::aio::Connection,
+        conn: &mut C,
     ) -> Result<T, redis::RedisError> {
-        self.query_async::<redis::aio::Connection, T>(conn).await
+        self.query_async::<C, T>(conn).await
     }
 }
@@ -0,0 +1,124 @@
+use crate::CiseauxSingle;
+
+use std::time::Duration;
+
+use rand::RngCore;
+use redis::RedisError;
+
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("del", KEYS[1])
+else
+    return 0
+end
+"#;
+
+const EXTEND_SCRIPT: &str = r#"
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("pexpire", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+fn random_token() -> Vec<u8> {
+    let mut token = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut token);
+    token.to_vec()
+}
+
+impl CiseauxSingle {
+    /// Tries to acquire a single-instance Redlock-style lock on `resource`, held for at most
+    /// `ttl` (the lock expires on its own if never released or extended). Returns `Ok(None)` if
+    /// the resource is already locked.
+    ///
+    /// This is the single-instance Redlock primitive only: there is no quorum across nodes, so
+    /// it's as reliable as the one Redis instance backing this pool, not as a real multi-master
+    /// Redlock deployment would be.
+    pub async fn lock(
+        &self,
+        resource: impl Into<String>,
+        ttl: Duration,
+    ) -> Result<Option<LockGuard>, RedisError> {
+        let resource = resource.into();
+        let token = random_token();
+        let acquired: Option<String> = self
+            .query(
+                redis::cmd("SET")
+                    .arg(&resource)
+                    .arg(&token)
+                    .arg("NX")
+                    .arg("PX")
+                    .arg(ttl.as_millis() as usize),
+            )
+            .await?;
+        Ok(acquired.map(|_| LockGuard {
+            pool: self.clone(),
+            resource,
+            token,
+        }))
+    }
+}
+
+/// A held lock acquired through CiseauxSingle::lock. Dropping it releases the lock (best effort,
+/// fire-and-forget); call release explicitly if you need to know whether it actually happened.
+pub struct LockGuard {
+    pool: CiseauxSingle,
+    resource: String,
+    token: Vec<u8>,
+}
+
+impl LockGuard {
+    /// Extends the lock's TTL to `ttl` from now, as long as it's still held by this guard's
+    /// token (i.e. it hasn't expired and been re-acquired by someone else in the meantime).
+    /// Returns `Ok(true)` if the TTL was extended, `Ok(false)` if the lock was no longer ours.
+    pub async fn extend(&self, ttl: Duration) -> Result<bool, RedisError> {
+        let extended: i64 = self
+            .pool
+            .query(
+                redis::cmd("EVAL")
+                    .arg(EXTEND_SCRIPT)
+                    .arg(1)
+                    .arg(&self.resource)
+                    .arg(&self.token)
+                    .arg(ttl.as_millis() as usize),
+            )
+            .await?;
+        Ok(extended == 1)
+    }
+
+    /// Releases the lock now, as long as it's still held by this guard's token. Returns
+    /// `Ok(true)` if it was released, `Ok(false)` if it was no longer ours to release.
+    pub async fn release(&self) -> Result<bool, RedisError> {
+        let released: i64 = self
+            .pool
+            .query(
+                redis::cmd("EVAL")
+                    .arg(RELEASE_SCRIPT)
+                    .arg(1)
+                    .arg(&self.resource)
+                    .arg(&self.token),
+            )
+            .await?;
+        Ok(released == 1)
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let pool = self.pool.clone();
+        let resource = std::mem::take(&mut self.resource);
+        let token = std::mem::take(&mut self.token);
+        crate::runtime::spawn(async move {
+            let _: Result<i64, RedisError> = pool
+                .query(
+                    redis::cmd("EVAL")
+                        .arg(RELEASE_SCRIPT)
+                        .arg(1)
+                        .arg(&resource)
+                        .arg(&token),
+                )
+                .await;
+        });
+    }
+}
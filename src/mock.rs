@@ -0,0 +1,256 @@
+//! An in-process fake backend for CiseauxSingle, enabled by the `mocks` cargo feature, so
+//! downstream users can unit-test code built on top of the pool without a running Redis
+//! instance. Queries go through the exact same `query<C: QueryAble, T>` entry point as a real
+//! pool: CiseauxSingle::mock() just backs the pool with a MockConnection instead of a real one.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use redis::{ErrorKind, RedisError, RedisFuture, RedisResult, Value};
+
+use crate::runtime::Mutex;
+use crate::single::Pool;
+use crate::{CiseauxSingle, ReconnectBehavior};
+use std::sync::atomic::AtomicUsize;
+
+/// One command as issued through CiseauxSingle::query against a mock backend: the uppercased
+/// command name, and its remaining arguments, in issue order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockCommand {
+    pub name: String,
+    pub args: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug)]
+enum Entry {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+}
+
+#[derive(Default)]
+struct MockState {
+    log: Vec<MockCommand>,
+    queued: VecDeque<RedisResult<Value>>,
+    data: HashMap<Vec<u8>, Entry>,
+}
+
+/// A handle to a mock backend's captured command log and programmable responses.
+#[derive(Clone)]
+pub struct MockHandle {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockHandle {
+    /// Queues one response to be returned by one upcoming query, bypassing the built-in
+    /// key/value and list emulation entirely. Queued responses are consumed first-in,
+    /// first-out, regardless of which command triggers them.
+    pub async fn push_response(&self, response: RedisResult<Value>) {
+        self.state.lock().await.queued.push_back(response);
+    }
+
+    /// Returns every command issued through the pool so far, in issue order.
+    pub async fn command_log(&self) -> Vec<MockCommand> {
+        self.state.lock().await.log.clone()
+    }
+
+    /// Clears the captured command log, keeping the in-memory data and any still-queued responses.
+    pub async fn clear_log(&self) {
+        self.state.lock().await.log.clear();
+    }
+}
+
+/// The connection-like handle CiseauxSingle drives a mock pool through. Cheaply Clone: every
+/// clone shares the same underlying state, the same way redis::aio::MultiplexedConnection does.
+#[derive(Clone)]
+pub(crate) struct MockConnection {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockConnection {
+    fn new() -> (MockConnection, MockHandle) {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        (
+            MockConnection {
+                state: state.clone(),
+            },
+            MockHandle { state },
+        )
+    }
+
+    async fn exec(&self, cmd: &redis::Cmd) -> RedisResult<Value> {
+        let args = flatten_args(cmd);
+        let name = args
+            .get(0)
+            .map(|a| String::from_utf8_lossy(a).to_uppercase())
+            .ok_or_else(|| RedisError::from((ErrorKind::ClientError, "empty command")))?;
+        let rest = args[1..].to_vec();
+
+        let mut state = self.state.lock().await;
+        state.log.push(MockCommand {
+            name: name.clone(),
+            args: rest.clone(),
+        });
+        if let Some(queued) = state.queued.pop_front() {
+            return queued;
+        }
+        emulate(&mut state.data, &name, &rest)
+    }
+}
+
+impl redis::aio::ConnectionLike for MockConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a redis::Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(self.exec(cmd))
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let mut results = Vec::with_capacity(count);
+            for c in cmd.cmd_iter().skip(offset).take(count) {
+                results.push(self.exec(c).await?);
+            }
+            Ok(results)
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
+
+fn flatten_args(cmd: &redis::Cmd) -> Vec<Vec<u8>> {
+    cmd.args_iter()
+        .map(|a| match a {
+            redis::Arg::Simple(bytes) => bytes.to_vec(),
+            redis::Arg::Cursor => b"0".to_vec(),
+        })
+        .collect()
+}
+
+fn emulate(data: &mut HashMap<Vec<u8>, Entry>, name: &str, args: &[Vec<u8>]) -> RedisResult<Value> {
+    match name {
+        "SET" => {
+            let key = arg(args, 0)?;
+            let value = arg(args, 1)?;
+            let nx = args[2..].iter().any(|a| a.eq_ignore_ascii_case(b"NX"));
+            if nx && data.contains_key(&key) {
+                return Ok(Value::Nil);
+            }
+            data.insert(key, Entry::String(value));
+            Ok(Value::Okay)
+        }
+        "GET" => Ok(match data.get(&arg(args, 0)?) {
+            Some(Entry::String(v)) => Value::Data(v.clone()),
+            _ => Value::Nil,
+        }),
+        "DEL" => {
+            let removed = args.iter().filter(|k| data.remove(*k).is_some()).count();
+            Ok(Value::Int(removed as i64))
+        }
+        "EXISTS" => {
+            let present = args.iter().filter(|k| data.contains_key(*k)).count();
+            Ok(Value::Int(present as i64))
+        }
+        "LPUSH" | "RPUSH" => {
+            let key = arg(args, 0)?;
+            let list = match data.entry(key).or_insert_with(|| Entry::List(VecDeque::new())) {
+                Entry::List(l) => l,
+                Entry::String(_) => return Err(wrong_type()),
+            };
+            for v in &args[1..] {
+                if name == "LPUSH" {
+                    list.push_front(v.clone());
+                } else {
+                    list.push_back(v.clone());
+                }
+            }
+            Ok(Value::Int(list.len() as i64))
+        }
+        "LLEN" => Ok(match data.get(&arg(args, 0)?) {
+            Some(Entry::List(l)) => Value::Int(l.len() as i64),
+            Some(Entry::String(_)) => return Err(wrong_type()),
+            None => Value::Int(0),
+        }),
+        "LRANGE" => {
+            let key = arg(args, 0)?;
+            let start = parse_i64(args, 1)?;
+            let stop = parse_i64(args, 2)?;
+            let list = match data.get(&key) {
+                Some(Entry::List(l)) => l,
+                Some(Entry::String(_)) => return Err(wrong_type()),
+                None => return Ok(Value::Bulk(Vec::new())),
+            };
+            Ok(Value::Bulk(
+                list_range(list, start, stop)
+                    .into_iter()
+                    .map(Value::Data)
+                    .collect(),
+            ))
+        }
+        _ => Err(RedisError::from((
+            ErrorKind::ClientError,
+            "mock backend doesn't emulate this command",
+        ))),
+    }
+}
+
+fn arg(args: &[Vec<u8>], idx: usize) -> RedisResult<Vec<u8>> {
+    args.get(idx)
+        .cloned()
+        .ok_or_else(|| RedisError::from((ErrorKind::ClientError, "missing argument")))
+}
+
+fn parse_i64(args: &[Vec<u8>], idx: usize) -> RedisResult<i64> {
+    std::str::from_utf8(&arg(args, idx)?)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| RedisError::from((ErrorKind::TypeError, "not an integer")))
+}
+
+fn wrong_type() -> RedisError {
+    RedisError::from((
+        ErrorKind::TypeError,
+        "WRONGTYPE Operation against a key holding the wrong kind of value",
+    ))
+}
+
+fn list_range(list: &VecDeque<Vec<u8>>, start: i64, stop: i64) -> Vec<Vec<u8>> {
+    let len = list.len() as i64;
+    // Not i64::clamp: that's only stable since Rust 1.50, newer than this crate's MSRV.
+    let index = |i: i64| -> i64 { if i < 0 { len + i } else { i } };
+    let clamp = |i: i64| -> i64 { i.max(0).min(len) };
+    // stop is inclusive: resolve a negative stop to its index before turning it exclusive.
+    let (start, stop) = (clamp(index(start)), clamp(index(stop) + 1));
+    if start >= stop {
+        return Vec::new();
+    }
+    list.iter()
+        .skip(start as usize)
+        .take((stop - start) as usize)
+        .cloned()
+        .collect()
+}
+
+impl CiseauxSingle {
+    /// Builds a CiseauxSingle pool backed by an in-process fake instead of a real Redis
+    /// connection, alongside a MockHandle to assert over the commands it receives and to
+    /// program canned responses. Requires the `mocks` cargo feature.
+    pub fn mock() -> (CiseauxSingle, MockHandle) {
+        let (conn, handle) = MockConnection::new();
+        // Never dialed by query(): Pool::Mock bypasses every real connection path, this only
+        // satisfies CiseauxSingle's client field, kept around for API symmetry with the real
+        // pool. subscribe()/psubscribe() still open a connection from it, and so fail on a mock.
+        let client = redis::Client::open("redis://mock-backend.invalid").unwrap();
+        let pool = CiseauxSingle::from_parts(
+            Arc::new(client),
+            ReconnectBehavior::default(),
+            Arc::new(Pool::Mock(conn)),
+            Arc::new(AtomicUsize::new(0)),
+        );
+        (pool, handle)
+    }
+}
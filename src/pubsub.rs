@@ -0,0 +1,200 @@
+use crate::ReconnectBehavior;
+
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use futures::{FutureExt, SinkExt, StreamExt};
+use redis::{Msg, RedisError};
+
+/// Commands sent from a Subscription handle to its background pub/sub driver task
+enum Control {
+    Subscribe(Vec<u8>),
+    PSubscribe(Vec<u8>),
+    Unsubscribe(Vec<u8>),
+    PUnsubscribe(Vec<u8>),
+    UnsubscribeAll,
+}
+
+/// A handle to add/remove channel and pattern subscriptions on a live subscription opened by
+/// CiseauxSingle::subscribe / CiseauxSingle::psubscribe.
+///
+/// Dropping every clone of the handle closes the control channel, which in turn tells the
+/// background driver task to unsubscribe and stop; dropping the paired message Stream has the
+/// same effect once the driver notices nobody is receiving messages anymore.
+#[derive(Clone)]
+pub struct Subscription {
+    control: mpsc::Sender<Control>,
+}
+
+impl Subscription {
+    /// Adds a channel subscription (SUBSCRIBE). A no-op if the driver task has already stopped.
+    pub async fn subscribe(&self, channel: impl Into<Vec<u8>>) {
+        let _ = self
+            .control
+            .clone()
+            .send(Control::Subscribe(channel.into()))
+            .await;
+    }
+
+    /// Adds a glob-style pattern subscription (PSUBSCRIBE). A no-op if the driver task has
+    /// already stopped.
+    pub async fn psubscribe(&self, pattern: impl Into<Vec<u8>>) {
+        let _ = self
+            .control
+            .clone()
+            .send(Control::PSubscribe(pattern.into()))
+            .await;
+    }
+
+    /// Removes a channel subscription (UNSUBSCRIBE).
+    pub async fn unsubscribe(&self, channel: impl Into<Vec<u8>>) {
+        let _ = self
+            .control
+            .clone()
+            .send(Control::Unsubscribe(channel.into()))
+            .await;
+    }
+
+    /// Removes a pattern subscription (PUNSUBSCRIBE).
+    pub async fn punsubscribe(&self, pattern: impl Into<Vec<u8>>) {
+        let _ = self
+            .control
+            .clone()
+            .send(Control::PUnsubscribe(pattern.into()))
+            .await;
+    }
+
+    /// Removes every channel and pattern subscription currently tracked by the driver task.
+    pub async fn unsubscribe_all(&self) {
+        let _ = self.control.clone().send(Control::UnsubscribeAll).await;
+    }
+}
+
+/// Opens a fresh pub/sub connection, issues the initial SUBSCRIBE/PSUBSCRIBE, and spawns the
+/// background task that owns the connection, drives reconnection, and forwards messages.
+pub(crate) async fn spawn(
+    client: Arc<redis::Client>,
+    reconnect_behavior: ReconnectBehavior,
+    channels: Vec<Vec<u8>>,
+    patterns: Vec<Vec<u8>>,
+) -> Result<(Subscription, mpsc::Receiver<Msg>), RedisError> {
+    let pubsub = open_and_subscribe(&client, &channels, &patterns).await?;
+    let (control_tx, control_rx) = mpsc::channel(32);
+    let (msg_tx, msg_rx) = mpsc::channel(256);
+    crate::runtime::spawn(driver(
+        client,
+        reconnect_behavior,
+        channels,
+        patterns,
+        pubsub,
+        control_rx,
+        msg_tx,
+    ));
+    Ok((
+        Subscription {
+            control: control_tx,
+        },
+        msg_rx,
+    ))
+}
+
+/// What woke the driver loop up, with every borrow of `pubsub`/`control_rx` resolved to an owned
+/// value: keeping those borrows alive past the `select!` itself would conflict with the `pubsub =
+/// p` reassignment the reconnect path needs to do.
+enum Event {
+    Control(Option<Control>),
+    Message(Option<Msg>),
+}
+
+async fn driver(
+    client: Arc<redis::Client>,
+    reconnect_behavior: ReconnectBehavior,
+    mut channels: Vec<Vec<u8>>,
+    mut patterns: Vec<Vec<u8>>,
+    mut pubsub: redis::aio::PubSub,
+    mut control_rx: mpsc::Receiver<Control>,
+    mut msg_tx: mpsc::Sender<Msg>,
+) {
+    loop {
+        let event = {
+            let mut ctrl_fut = control_rx.next().fuse();
+            let mut msg_fut = pubsub.on_message().next().fuse();
+            futures::select! {
+                ctrl = ctrl_fut => Event::Control(ctrl),
+                msg = msg_fut => Event::Message(msg),
+            }
+        };
+        match event {
+            Event::Control(ctrl) => match ctrl {
+                Some(Control::Subscribe(c)) => {
+                    if pubsub.subscribe(&c).await.is_ok() {
+                        channels.push(c);
+                    }
+                }
+                Some(Control::PSubscribe(p)) => {
+                    if pubsub.psubscribe(&p).await.is_ok() {
+                        patterns.push(p);
+                    }
+                }
+                Some(Control::Unsubscribe(c)) => {
+                    let _ = pubsub.unsubscribe(&c).await;
+                    channels.retain(|x| x != &c);
+                }
+                Some(Control::PUnsubscribe(p)) => {
+                    let _ = pubsub.punsubscribe(&p).await;
+                    patterns.retain(|x| x != &p);
+                }
+                Some(Control::UnsubscribeAll) => {
+                    for c in channels.drain(..) {
+                        let _ = pubsub.unsubscribe(&c).await;
+                    }
+                    for p in patterns.drain(..) {
+                        let _ = pubsub.punsubscribe(&p).await;
+                    }
+                }
+                // Every Subscription handle was dropped, nobody can add/remove subscriptions
+                // anymore: tear down.
+                None => return,
+            },
+            Event::Message(msg) => match msg {
+                Some(m) => {
+                    // Nobody is polling the message Stream anymore: tear down.
+                    if msg_tx.send(m).await.is_err() {
+                        return;
+                    }
+                }
+                None => {
+                    if reconnect_behavior == ReconnectBehavior::NoReconnect {
+                        return;
+                    }
+                    let mut reopened = open_and_subscribe(&client, &channels, &patterns).await;
+                    if let ReconnectBehavior::RetryWaitRetry(d) = reconnect_behavior {
+                        if reopened.is_err() {
+                            crate::runtime::sleep(d.unwrap_or(crate::DEFAULT_WAIT_RETRY_DUR)).await;
+                            reopened = open_and_subscribe(&client, &channels, &patterns).await;
+                        }
+                    }
+                    match reopened {
+                        Ok(p) => pubsub = p,
+                        Err(_) => return,
+                    }
+                }
+            },
+        }
+    }
+}
+
+async fn open_and_subscribe(
+    client: &redis::Client,
+    channels: &[Vec<u8>],
+    patterns: &[Vec<u8>],
+) -> Result<redis::aio::PubSub, RedisError> {
+    let mut pubsub = crate::runtime::get_connection(client).await?.into_pubsub();
+    for c in channels {
+        pubsub.subscribe(c).await?;
+    }
+    for p in patterns {
+        pubsub.psubscribe(p).await?;
+    }
+    Ok(pubsub)
+}
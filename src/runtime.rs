@@ -0,0 +1,75 @@
+//! A tiny shim around the handful of tokio/async-std specific calls the pools need (sleeping
+//! between reconnect attempts, and acquiring connections), so the same pool code works under
+//! either runtime depending on which of the `runtime-tokio` / `runtime-async-std` cargo features
+//! is enabled. `runtime-tokio` is the default; enabling `runtime-async-std` instead (and
+//! disabling default features) forwards to redis-rs's `async-std-comp`.
+
+use std::time::Duration;
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) use tokio::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "runtime-async-std")]
+pub(crate) use async_std::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::delay_for(duration).await;
+}
+
+#[cfg(feature = "runtime-async-std")]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await;
+}
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn get_connection(
+    client: &redis::Client,
+) -> Result<redis::aio::Connection, redis::RedisError> {
+    client.get_async_connection().await
+}
+
+#[cfg(feature = "runtime-async-std")]
+pub(crate) async fn get_connection(
+    client: &redis::Client,
+) -> Result<redis::aio::Connection, redis::RedisError> {
+    client.get_async_std_connection().await
+}
+
+/// Spawns a task that runs to completion independently, detached from its caller. Used to drive
+/// background work (the multiplexed connection's write-out task, the pub/sub driver loop).
+#[cfg(feature = "runtime-tokio")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future);
+}
+
+#[cfg(feature = "runtime-async-std")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    async_std::task::spawn(future);
+}
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn get_multiplexed_connection(
+    client: &redis::Client,
+) -> Result<redis::aio::MultiplexedConnection, redis::RedisError> {
+    let (conn, driver) = client.create_multiplexed_tokio_connection().await?;
+    spawn(driver);
+    Ok(conn)
+}
+
+#[cfg(feature = "runtime-async-std")]
+pub(crate) async fn get_multiplexed_connection(
+    client: &redis::Client,
+) -> Result<redis::aio::MultiplexedConnection, redis::RedisError> {
+    let (conn, driver) = client.create_multiplexed_async_std_connection().await?;
+    spawn(driver);
+    Ok(conn)
+}
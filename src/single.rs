@@ -1,12 +1,14 @@
-use crate::{ConnectionsCount, QueryAble, ReconnectBehavior};
+use crate::runtime::{Mutex, MutexGuard};
+use crate::{ConnectionsCount, QueryAble, ReconnectBehavior, Subscription};
 
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc,
+    Arc, Weak,
 };
-use tokio::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
-use redis::RedisError;
+use futures::Stream;
+use redis::{Msg, RedisError};
 
 /// An Init Struct to create a customized CiseauxSingle connections pool.
 /// This is like a Builder, but using public fields instead of functions
@@ -18,6 +20,40 @@ pub struct SingleInit {
     pub conns_count: ConnectionsCount,
     /// By default, Instant Retry
     pub reconnect_behavior: ReconnectBehavior,
+    /// When true, the pool is built out of cloneable redis::aio::MultiplexedConnection
+    /// handles instead of Mutex<redis::aio::Connection>, so a query only takes the
+    /// Mutex for the time it takes to clone a handle, not for the whole round trip.
+    /// By default, false.
+    pub multiplexed: bool,
+    /// Background health-checking and on-checkout validation, disabled by default.
+    pub health_check: HealthCheck,
+}
+
+/// Background health-checking and on-checkout validation for a CiseauxSingle pool, so a
+/// connection broken by a server restart or an idle timeout is caught and recycled before it
+/// ever surfaces an error to a caller of query().
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthCheck {
+    /// If Some, a background task wakes up on this interval, PINGs every idle connection in the
+    /// pool, and transparently replaces any that fails (or that has exceeded max_lifetime).
+    /// Required for max_lifetime to have any effect, since it's the same task that enforces it.
+    pub ping_interval: Option<Duration>,
+    /// If Some, a connection older than this is proactively recreated by the background task,
+    /// even if it still answers PING. Checked on the same tick as ping_interval.
+    pub max_lifetime: Option<Duration>,
+    /// If true, query() PINGs the connection it checks out before running the caller's command,
+    /// and transparently reconnects if that PING fails.
+    pub validate_on_checkout: bool,
+}
+
+impl std::default::Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck {
+            ping_interval: None,
+            max_lifetime: None,
+            validate_on_checkout: false,
+        }
+    }
 }
 
 impl SingleInit {
@@ -27,6 +63,8 @@ impl SingleInit {
             client,
             conns_count: ConnectionsCount::default(),
             reconnect_behavior: ReconnectBehavior::default(),
+            multiplexed: false,
+            health_check: HealthCheck::default(),
         }
     }
 
@@ -36,35 +74,177 @@ impl SingleInit {
             client: redis::Client::open("redis://127.0.0.1:6379").unwrap(), // Unwrap is OK since client open doesn't connect, but only checks URL Validity.
             conns_count: ConnectionsCount::default(),
             reconnect_behavior: ReconnectBehavior::default(),
+            multiplexed: false,
+            health_check: HealthCheck::default(),
         }
     }
 
     /// Asynchronously creates multiple connexions to a Redis instance
     pub async fn build(self) -> Result<CiseauxSingle, RedisError> {
         let conns_count = self.conns_count.into_flat();
-        let mut conns_fut = Vec::with_capacity(conns_count);
-        for _ in 0..conns_count {
-            conns_fut.push(self.client.get_async_connection());
-        }
-        let mut conns = Vec::with_capacity(conns_count);
-        for c in futures::future::join_all(conns_fut).await {
-            conns.push(Mutex::new(c?));
+        let conns = if self.multiplexed {
+            Pool::Multiplexed(build_multiplexed_conns(&self.client, conns_count).await?)
+        } else {
+            Pool::Plain(build_plain_conns(&self.client, conns_count).await?)
+        };
+        let client = Arc::new(self.client);
+        let conns = Arc::new(conns);
+        if self.health_check.ping_interval.is_some() {
+            crate::runtime::spawn(health_check_task(
+                Arc::downgrade(&conns),
+                client.clone(),
+                self.health_check,
+            ));
         }
         Ok(CiseauxSingle {
-            client: Arc::new(self.client),
+            client,
             reconnect_behavior: self.reconnect_behavior,
-            conns: Arc::new(conns),
+            health_check: self.health_check,
+            conns,
             next: Arc::new(AtomicUsize::new(0)),
         })
     }
 }
 
+/// A pooled connection, tagged with when it was established so max_lifetime can be enforced.
+struct Slot<T> {
+    conn: T,
+    created_at: Instant,
+}
+
+impl<T> Slot<T> {
+    fn new(conn: T) -> Slot<T> {
+        Slot {
+            conn,
+            created_at: Instant::now(),
+        }
+    }
+}
+
+async fn build_plain_conns(
+    client: &redis::Client,
+    count: usize,
+) -> Result<Vec<Mutex<Slot<redis::aio::Connection>>>, RedisError> {
+    let mut conns_fut = Vec::with_capacity(count);
+    for _ in 0..count {
+        conns_fut.push(crate::runtime::get_connection(client));
+    }
+    let mut conns = Vec::with_capacity(count);
+    for c in futures::future::join_all(conns_fut).await {
+        conns.push(Mutex::new(Slot::new(c?)));
+    }
+    Ok(conns)
+}
+
+async fn build_multiplexed_conns(
+    client: &redis::Client,
+    count: usize,
+) -> Result<Vec<Mutex<Slot<redis::aio::MultiplexedConnection>>>, RedisError> {
+    let mut conns_fut = Vec::with_capacity(count);
+    for _ in 0..count {
+        conns_fut.push(crate::runtime::get_multiplexed_connection(client));
+    }
+    let mut conns = Vec::with_capacity(count);
+    for c in futures::future::join_all(conns_fut).await {
+        conns.push(Mutex::new(Slot::new(c?)));
+    }
+    Ok(conns)
+}
+
+/// The ways CiseauxSingle can hold its underlying connections, picked by SingleInit::multiplexed,
+/// or by going through CiseauxSingle::mock() instead of SingleInit entirely.
+pub(crate) enum Pool {
+    /// One redis::aio::Connection per slot, held for the whole query round trip
+    Plain(Vec<Mutex<Slot<redis::aio::Connection>>>),
+    /// One redis::aio::MultiplexedConnection per slot, only locked long enough to clone a handle
+    Multiplexed(Vec<Mutex<Slot<redis::aio::MultiplexedConnection>>>),
+    /// A single shared in-process fake backend, see CiseauxSingle::mock
+    #[cfg(feature = "mocks")]
+    Mock(crate::mock::MockConnection),
+}
+
+impl Pool {
+    fn len(&self) -> usize {
+        match self {
+            Pool::Plain(conns) => conns.len(),
+            Pool::Multiplexed(conns) => conns.len(),
+            #[cfg(feature = "mocks")]
+            Pool::Mock(_) => 1,
+        }
+    }
+}
+
+/// Periodically PINGs every idle connection in the pool and transparently replaces any that
+/// fails to answer, or that has outlived health_check.max_lifetime. Stops once every
+/// CiseauxSingle handle sharing this pool has been dropped.
+async fn health_check_task(conns: Weak<Pool>, client: Arc<redis::Client>, health_check: HealthCheck) {
+    let interval = match health_check.ping_interval {
+        Some(d) => d,
+        None => return,
+    };
+    loop {
+        crate::runtime::sleep(interval).await;
+        let pool = match conns.upgrade() {
+            Some(pool) => pool,
+            None => return,
+        };
+        match &*pool {
+            Pool::Plain(slots) => {
+                for slot in slots.iter() {
+                    let mut guard = slot.lock().await;
+                    if needs_recycling(&guard, health_check)
+                        || redis::cmd("PING")
+                            .query_async::<_, String>(&mut guard.conn)
+                            .await
+                            .is_err()
+                    {
+                        if let Ok(conn) = crate::runtime::get_connection(&client).await {
+                            *guard = Slot::new(conn);
+                        }
+                    }
+                }
+            }
+            Pool::Multiplexed(slots) => {
+                for slot in slots.iter() {
+                    // Only held long enough to clone a handle and check created_at: the PING
+                    // round trip itself runs without the Mutex guard in scope, so a concurrent
+                    // query() never blocks on the health check.
+                    let (mut conn, needs_recycling) = {
+                        let guard = slot.lock().await;
+                        (guard.conn.clone(), needs_recycling(&guard, health_check))
+                    };
+                    let failed = redis::cmd("PING")
+                        .query_async::<_, String>(&mut conn)
+                        .await
+                        .is_err();
+                    if needs_recycling || failed {
+                        if let Ok(conn) = crate::runtime::get_multiplexed_connection(&client).await
+                        {
+                            *slot.lock().await = Slot::new(conn);
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "mocks")]
+            Pool::Mock(_) => {}
+        }
+    }
+}
+
+fn needs_recycling<T>(slot: &Slot<T>, health_check: HealthCheck) -> bool {
+    match health_check.max_lifetime {
+        Some(max_lifetime) => slot.created_at.elapsed() >= max_lifetime,
+        None => false,
+    }
+}
+
 /// A connections pool to a single Redis instance
 #[derive(Clone)]
 pub struct CiseauxSingle {
     client: Arc<redis::Client>,
     reconnect_behavior: ReconnectBehavior,
-    conns: Arc<Vec<Mutex<redis::aio::Connection>>>,
+    health_check: HealthCheck,
+    conns: Arc<Pool>,
     next: Arc<AtomicUsize>,
 }
 
@@ -84,6 +264,60 @@ impl CiseauxSingle {
         SingleInit::new(client).build().await
     }
 
+    /// Assembles a CiseauxSingle from already-built parts, bypassing SingleInit. Used by
+    /// CiseauxSingle::mock, which has no real redis::Client connections to build.
+    #[cfg(feature = "mocks")]
+    pub(crate) fn from_parts(
+        client: Arc<redis::Client>,
+        reconnect_behavior: ReconnectBehavior,
+        conns: Arc<Pool>,
+        next: Arc<AtomicUsize>,
+    ) -> CiseauxSingle {
+        CiseauxSingle {
+            client,
+            reconnect_behavior,
+            health_check: HealthCheck::default(),
+            conns,
+            next,
+        }
+    }
+
+    /// Subscribes to the given channels (SUBSCRIBE) on a connection of its own: pub/sub
+    /// connections can't be shared with the request/response pool. Returns a Subscription
+    /// handle to add/remove subscriptions, and a Stream of the redis::Msg values received.
+    /// If the socket drops, it's transparently re-established (following reconnect_behavior)
+    /// and every subscription active at the time is replayed.
+    pub async fn subscribe(
+        &self,
+        channels: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+    ) -> Result<(Subscription, impl Stream<Item = Msg>), RedisError> {
+        let channels = channels.into_iter().map(Into::into).collect();
+        let (sub, rx) = crate::pubsub::spawn(
+            self.client.clone(),
+            self.reconnect_behavior,
+            channels,
+            Vec::new(),
+        )
+        .await?;
+        Ok((sub, rx))
+    }
+
+    /// Like subscribe, but for glob-style channel patterns (PSUBSCRIBE).
+    pub async fn psubscribe(
+        &self,
+        patterns: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+    ) -> Result<(Subscription, impl Stream<Item = Msg>), RedisError> {
+        let patterns = patterns.into_iter().map(Into::into).collect();
+        let (sub, rx) = crate::pubsub::spawn(
+            self.client.clone(),
+            self.reconnect_behavior,
+            Vec::new(),
+            patterns,
+        )
+        .await?;
+        Ok((sub, rx))
+    }
+
     /// Asynchronously query QueryAble (trait, implemented for redis::Cmd and redis::Pipeline),
     /// but in case of network error, will try to reconnect once to the same database (by default),
     /// or follow the reconnect_behavior you provided
@@ -91,39 +325,95 @@ impl CiseauxSingle {
         &self,
         cmd: C,
     ) -> Result<T, RedisError> {
-        let mut conn = self.conns[self.next.fetch_add(1, Ordering::AcqRel) % self.conns.len()]
-            .lock()
-            .await;
-        match cmd.query::<T>(&mut conn).await {
-            Ok(v) => Ok(v),
-            Err(e) => {
-                if is_network_or_io_error(&e) {
-                    if self.reconnect_behavior == ReconnectBehavior::NoReconnect {
-                        return Err(e);
+        let idx = self.next.fetch_add(1, Ordering::AcqRel) % self.conns.len();
+        match &*self.conns {
+            Pool::Plain(conns) => {
+                let mut conn = conns[idx].lock().await;
+                if self.health_check.validate_on_checkout {
+                    self.validate_plain(&mut conn).await;
+                }
+                match cmd.query::<T, _>(&mut conn.conn).await {
+                    Ok(v) => Ok(v),
+                    Err(e) => {
+                        if crate::is_network_or_io_error(&e) {
+                            if self.reconnect_behavior == ReconnectBehavior::NoReconnect {
+                                return Err(e);
+                            }
+                            return self.retry_plain_cmd(&mut conn, cmd).await;
+                        }
+                        Err(e)
                     }
-                    return self.retry_cmd(&mut conn, cmd).await;
                 }
-                return Err(e);
+            }
+            Pool::Multiplexed(conns) => {
+                // Only held long enough to clone a cheap, shareable handle: the query itself
+                // runs without the Mutex guard in scope, so concurrent callers never block on it.
+                if self.health_check.validate_on_checkout {
+                    self.validate_multiplexed(&conns[idx]).await;
+                }
+                let mut conn = conns[idx].lock().await.conn.clone();
+                match cmd.query::<T, _>(&mut conn).await {
+                    Ok(v) => Ok(v),
+                    Err(e) => {
+                        if crate::is_network_or_io_error(&e) {
+                            if self.reconnect_behavior == ReconnectBehavior::NoReconnect {
+                                return Err(e);
+                            }
+                            return self.retry_multiplexed_cmd(&conns[idx], cmd).await;
+                        }
+                        Err(e)
+                    }
+                }
+            }
+            #[cfg(feature = "mocks")]
+            Pool::Mock(conn) => {
+                let mut conn = conn.clone();
+                cmd.query::<T, _>(&mut conn).await
             }
         }
     }
 
     #[inline(always)]
-    async fn retry_cmd<'a, C: QueryAble, T: redis::FromRedisValue>(
+    async fn validate_plain<'a>(&self, conn: &mut MutexGuard<'a, Slot<redis::aio::Connection>>) {
+        if redis::cmd("PING")
+            .query_async::<_, String>(&mut conn.conn)
+            .await
+            .is_err()
+        {
+            let _ = self.try_reconnect_plain(conn).await;
+        }
+    }
+
+    #[inline(always)]
+    async fn validate_multiplexed(&self, slot: &Mutex<Slot<redis::aio::MultiplexedConnection>>) {
+        // Only held long enough to clone a handle: the PING round trip itself runs without the
+        // Mutex guard in scope, so concurrent callers never block on it.
+        let mut conn = slot.lock().await.conn.clone();
+        let ok = redis::cmd("PING")
+            .query_async::<_, String>(&mut conn)
+            .await
+            .is_ok();
+        if !ok {
+            let _ = self.try_reconnect_multiplexed(slot).await;
+        }
+    }
+
+    #[inline(always)]
+    async fn retry_plain_cmd<'a, C: QueryAble, T: redis::FromRedisValue>(
         &self,
-        conn: &mut MutexGuard<'a, redis::aio::Connection>,
+        conn: &mut MutexGuard<'a, Slot<redis::aio::Connection>>,
         cmd: C,
     ) -> Result<T, RedisError> {
-        match self.try_reconnect(conn).await {
-            Ok(()) => return cmd.query::<T>(conn).await,
+        match self.try_reconnect_plain(conn).await {
+            Ok(()) => return cmd.query::<T, _>(&mut conn.conn).await,
             Err(e) => {
-                if is_network_or_io_error(&e) {
+                if crate::is_network_or_io_error(&e) {
                     match self.reconnect_behavior {
                         ReconnectBehavior::RetryWaitRetry(d) => {
-                            tokio::time::delay_for(d.unwrap_or(crate::DEFAULT_WAIT_RETRY_DUR))
+                            crate::runtime::sleep(d.unwrap_or(crate::DEFAULT_WAIT_RETRY_DUR))
                                 .await;
-                            self.try_reconnect(conn).await?;
-                            return cmd.query::<T>(conn).await;
+                            self.try_reconnect_plain(conn).await?;
+                            return cmd.query::<T, _>(&mut conn.conn).await;
                         }
                         _ => return Err(e),
                     }
@@ -134,23 +424,51 @@ impl CiseauxSingle {
     }
 
     #[inline(always)]
-    async fn try_reconnect<'a>(
+    async fn try_reconnect_plain<'a>(
         &self,
-        conn: &mut MutexGuard<'a, redis::aio::Connection>,
+        conn: &mut MutexGuard<'a, Slot<redis::aio::Connection>>,
     ) -> Result<(), RedisError> {
-        match self.client.get_async_connection().await {
+        match crate::runtime::get_connection(&self.client).await {
             Ok(c) => {
-                **conn = c;
+                **conn = Slot::new(c);
                 Ok(())
             }
             Err(e) => Err(e),
         }
     }
-}
 
-fn is_network_or_io_error(error: &RedisError) -> bool {
-    if error.is_timeout() || error.is_connection_dropped() || error.is_io_error() {
-        return true;
+    #[inline(always)]
+    async fn retry_multiplexed_cmd<C: QueryAble, T: redis::FromRedisValue>(
+        &self,
+        slot: &Mutex<Slot<redis::aio::MultiplexedConnection>>,
+        cmd: C,
+    ) -> Result<T, RedisError> {
+        match self.try_reconnect_multiplexed(slot).await {
+            Ok(mut conn) => return cmd.query::<T, _>(&mut conn).await,
+            Err(e) => {
+                if crate::is_network_or_io_error(&e) {
+                    match self.reconnect_behavior {
+                        ReconnectBehavior::RetryWaitRetry(d) => {
+                            crate::runtime::sleep(d.unwrap_or(crate::DEFAULT_WAIT_RETRY_DUR))
+                                .await;
+                            let mut conn = self.try_reconnect_multiplexed(slot).await?;
+                            return cmd.query::<T, _>(&mut conn).await;
+                        }
+                        _ => return Err(e),
+                    }
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    #[inline(always)]
+    async fn try_reconnect_multiplexed(
+        &self,
+        slot: &Mutex<Slot<redis::aio::MultiplexedConnection>>,
+    ) -> Result<redis::aio::MultiplexedConnection, RedisError> {
+        let conn = crate::runtime::get_multiplexed_connection(&self.client).await?;
+        *slot.lock().await = Slot::new(conn.clone());
+        Ok(conn)
     }
-    false
 }
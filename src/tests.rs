@@ -10,6 +10,8 @@ async fn try_single() {
             client: redis_client,
             conns_count: ConnectionsCount::Global(1),
             reconnect_behavior: ReconnectBehavior::NoReconnect,
+            multiplexed: false,
+            health_check: Default::default(),
         };
         init.build().await.expect("Failed to build CiseauxSingle")
     };
@@ -23,3 +25,181 @@ async fn try_single() {
         .expect("try_single GET failed");
     assert!(hello == HELLO_VALUE);
 }
+
+#[tokio::test]
+async fn try_single_multiplexed() {
+    const HELLO_VALUE: &'static str = "qwertyuiop";
+    use crate::{single::SingleInit, ConnectionsCount, ReconnectBehavior};
+    let redis_client =
+        redis::Client::open("redis://127.0.0.1:6379").expect("Failed to create redis::Client");
+    let db_pool = {
+        let init = SingleInit {
+            client: redis_client,
+            conns_count: ConnectionsCount::Global(1),
+            reconnect_behavior: ReconnectBehavior::NoReconnect,
+            multiplexed: true,
+            health_check: Default::default(),
+        };
+        init.build().await.expect("Failed to build CiseauxSingle")
+    };
+    assert!(db_pool
+        .query::<_, ()>(redis::Cmd::set(
+            "ciseaux_client_tests_hello_multiplexed",
+            HELLO_VALUE
+        ))
+        .await
+        .is_ok());
+    let hello = db_pool
+        .query::<_, String>(&redis::Cmd::get("ciseaux_client_tests_hello_multiplexed"))
+        .await
+        .expect("try_single_multiplexed GET failed");
+    assert!(hello == HELLO_VALUE);
+}
+
+#[tokio::test]
+async fn try_single_health_check() {
+    const HELLO_VALUE: &'static str = "qwertyuiop";
+    use crate::single::{HealthCheck, SingleInit};
+    use crate::{ConnectionsCount, ReconnectBehavior};
+    use std::time::Duration;
+    let redis_client =
+        redis::Client::open("redis://127.0.0.1:6379").expect("Failed to create redis::Client");
+    let db_pool = {
+        let init = SingleInit {
+            client: redis_client,
+            conns_count: ConnectionsCount::Global(1),
+            reconnect_behavior: ReconnectBehavior::NoReconnect,
+            multiplexed: true,
+            health_check: HealthCheck {
+                // Short enough that the background task ticks (and recycles the only
+                // connection, since it's already older than max_lifetime) well within the
+                // test's 200ms wait below.
+                ping_interval: Some(Duration::from_millis(50)),
+                max_lifetime: Some(Duration::from_millis(1)),
+                validate_on_checkout: true,
+            },
+        };
+        init.build().await.expect("Failed to build CiseauxSingle")
+    };
+
+    // Wait past at least one background tick, so the sole pooled connection gets recycled.
+    tokio::time::delay_for(Duration::from_millis(200)).await;
+
+    // The pool must still be usable after the background task swapped its connection out.
+    assert!(db_pool
+        .query::<_, ()>(redis::Cmd::set(
+            "ciseaux_client_tests_hello_health_check",
+            HELLO_VALUE
+        ))
+        .await
+        .is_ok());
+    let hello = db_pool
+        .query::<_, String>(&redis::Cmd::get("ciseaux_client_tests_hello_health_check"))
+        .await
+        .expect("try_single_health_check GET failed");
+    assert!(hello == HELLO_VALUE);
+}
+
+#[tokio::test]
+async fn try_single_pubsub() {
+    use crate::single::SingleInit;
+    use crate::{ConnectionsCount, ReconnectBehavior};
+    use futures::StreamExt;
+
+    let redis_client =
+        redis::Client::open("redis://127.0.0.1:6379").expect("Failed to create redis::Client");
+    let db_pool = {
+        let init = SingleInit {
+            client: redis_client,
+            conns_count: ConnectionsCount::Global(1),
+            reconnect_behavior: ReconnectBehavior::NoReconnect,
+            multiplexed: false,
+            health_check: Default::default(),
+        };
+        init.build().await.expect("Failed to build CiseauxSingle")
+    };
+
+    let (_sub, mut messages) = db_pool
+        .subscribe(vec!["ciseaux_client_tests_channel"])
+        .await
+        .expect("Failed to subscribe");
+
+    // The subscribe connection is separate from the pool, so publish through it.
+    assert!(db_pool
+        .query::<_, i32>(
+            redis::Cmd::new()
+                .arg("PUBLISH")
+                .arg("ciseaux_client_tests_channel")
+                .arg("qwertyuiop")
+        )
+        .await
+        .is_ok());
+
+    let msg = messages
+        .next()
+        .await
+        .expect("Expected a message on the subscribed channel");
+    assert_eq!(msg.get_payload::<String>().unwrap(), "qwertyuiop");
+}
+
+#[tokio::test]
+async fn try_single_lock() {
+    use crate::single::SingleInit;
+    use crate::{ConnectionsCount, ReconnectBehavior};
+    use std::time::Duration;
+
+    let redis_client =
+        redis::Client::open("redis://127.0.0.1:6379").expect("Failed to create redis::Client");
+    let db_pool = {
+        let init = SingleInit {
+            client: redis_client,
+            conns_count: ConnectionsCount::Global(1),
+            reconnect_behavior: ReconnectBehavior::NoReconnect,
+            multiplexed: false,
+            health_check: Default::default(),
+        };
+        init.build().await.expect("Failed to build CiseauxSingle")
+    };
+
+    let guard = db_pool
+        .lock("ciseaux_client_tests_lock", Duration::from_secs(30))
+        .await
+        .expect("lock() failed")
+        .expect("Expected the lock to be acquired");
+
+    let contended = db_pool
+        .lock("ciseaux_client_tests_lock", Duration::from_secs(30))
+        .await
+        .expect("lock() failed");
+    assert!(contended.is_none());
+
+    assert!(guard.release().await.expect("release() failed"));
+
+    let reacquired = db_pool
+        .lock("ciseaux_client_tests_lock", Duration::from_secs(30))
+        .await
+        .expect("lock() failed");
+    assert!(reacquired.is_some());
+}
+
+#[cfg(feature = "mocks")]
+#[tokio::test]
+async fn try_single_mock() {
+    use crate::CiseauxSingle;
+
+    let (db_pool, handle) = CiseauxSingle::mock();
+    assert!(db_pool
+        .query::<_, ()>(redis::Cmd::set("ciseaux_client_tests_hello", "qwertyuiop"))
+        .await
+        .is_ok());
+    let hello = db_pool
+        .query::<_, String>(&redis::Cmd::get("ciseaux_client_tests_hello"))
+        .await
+        .expect("try_single_mock GET failed");
+    assert!(hello == "qwertyuiop");
+
+    let log = handle.command_log().await;
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].name, "SET");
+    assert_eq!(log[1].name, "GET");
+}